@@ -8,8 +8,9 @@ use crossterm::{
     ExecutableCommand, cursor,
     terminal::{Clear, ClearType},
 };
-use glam::{Mat3, Vec3, mat3, vec3};
-use objects::torus;
+use glam::{Mat3, Vec3, vec3};
+use lighting::PointLight;
+use objects::{Material, Sdf, torus};
 use raymarch::RaymarchOptions;
 
 mod raymarch {
@@ -32,6 +33,14 @@ mod raymarch {
         pub far_clip: f32,
         pub epsilon: f32,
         pub normal_epsilon: f32,
+        /// Maximum depth of reflection/refraction recursion in
+        /// `raytrace::trace`.
+        pub max_bounces: usize,
+        /// Over-relaxation step multiplier in `[1, 2]` used by
+        /// [`raymarch`]. `1.0` is plain sphere tracing; values above that
+        /// take larger, accelerated steps at the cost of occasionally
+        /// overshooting thin features, which is detected and corrected.
+        pub omega: f32,
     }
 
     impl Default for RaymarchOptions {
@@ -41,6 +50,8 @@ mod raymarch {
                 far_clip: 1e3,
                 epsilon: 1e-4,
                 normal_epsilon: 1e-3,
+                max_bounces: 4,
+                omega: 1.5,
             }
         }
     }
@@ -71,14 +82,14 @@ mod raymarch {
         let mut nearest_distance = options.far_clip;
         for i in 0..options.max_iterations {
             let scene_pos = ray.at(depth);
-            let scene_distance = (scene)(scene_pos);
-            nearest_distance = nearest_distance.min(scene_distance);
+            let r_cur = (scene)(scene_pos);
+            nearest_distance = nearest_distance.min(r_cur);
             if depth > options.far_clip {
                 return MissedScene {
                     iterations: i,
                     nearest_distance,
                 };
-            } else if scene_distance < options.epsilon {
+            } else if r_cur < options.epsilon {
                 let offset_vec = vec2(options.normal_epsilon, 0.0);
                 let x = (scene)(scene_pos + offset_vec.xyy());
                 let y = (scene)(scene_pos + offset_vec.yxy());
@@ -89,8 +100,17 @@ mod raymarch {
                     depth,
                     normal,
                 };
+            }
+
+            let step = options.omega * r_cur;
+            if options.omega > 1.0 && (scene)(ray.at(depth + step)) + r_cur < step {
+                // The unbounding spheres at the start and end of this step no
+                // longer overlap: we likely skipped past a thin feature.
+                // Undo the accelerated move and fall back to a plain,
+                // conservative sphere-tracing step for this iteration.
+                depth += r_cur;
             } else {
-                depth += scene_distance;
+                depth += step;
             }
         }
         ReachedMaxIterations { nearest_distance }
@@ -115,110 +135,1214 @@ mod objects {
         .length();
         q - minor_radius
     }
+
+    pub fn sphere(pos: Vec3, origin: Vec3, radius: f32) -> f32 {
+        (pos - origin).length() - radius
+    }
+
+    pub fn cuboid(pos: Vec3, origin: Vec3, half_extents: Vec3) -> f32 {
+        let q = (pos - origin).abs() - half_extents;
+        q.max(Vec3::ZERO).length() + q.x.max(q.y).max(q.z).min(0.0)
+    }
+
+    pub fn plane(pos: Vec3, origin: Vec3, normal: Vec3) -> f32 {
+        (pos - origin).dot(normal)
+    }
+
+    /// Smoothly blends two distances together, rounding off the seam where
+    /// two primitives would otherwise meet with a hard edge. `k` controls
+    /// the size of the blended region.
+    pub fn smin(a: f32, b: f32, k: f32) -> f32 {
+        let h = (k - (a - b).abs()).max(0.0) / k;
+        a.min(b) - h * h * k * 0.25
+    }
+
+    /// Surface response of an [`Sdf`] primitive: how much of a secondary ray
+    /// `raytrace::trace` should spawn at a hit, and whether that ray
+    /// reflects off the surface or refracts through it as a dielectric.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Material {
+        /// Fraction of a mirror-reflected ray mixed into the shaded color;
+        /// `0.0` is fully matte.
+        pub reflectivity: f32,
+        /// Index of refraction; `Some` makes the surface a dielectric that
+        /// refracts (and Fresnel-reflects) instead of just reflecting.
+        pub ior: Option<f32>,
+        /// Diffuse reflectance, multiplied into a path's throughput at each
+        /// bounce in `pathtrace::trace_path`.
+        pub albedo: Vec3,
+    }
+
+    impl Material {
+        pub const MATTE: Material = Material {
+            reflectivity: 0.0,
+            ior: None,
+            albedo: Vec3::splat(0.8),
+        };
+
+        pub fn mirror(reflectivity: f32) -> Self {
+            Self {
+                reflectivity,
+                ..Self::MATTE
+            }
+        }
+
+        pub fn dielectric(ior: f32) -> Self {
+            Self {
+                ior: Some(ior),
+                ..Self::MATTE
+            }
+        }
+
+        pub fn with_albedo(self, albedo: Vec3) -> Self {
+            Self { albedo, ..self }
+        }
+    }
+
+    impl Default for Material {
+        fn default() -> Self {
+            Self::MATTE
+        }
+    }
+
+    /// A composable signed-distance-field scene graph: primitives at the
+    /// leaves, boolean combinators at the branches. Call [`Sdf::distance`]
+    /// to evaluate the whole tree at a point, and feed that straight into
+    /// [`crate::camera::Camera::render_parallel`]; call [`Sdf::distance_material`] to
+    /// also recover the [`Material`] of whichever primitive is nearest.
+    pub enum Sdf {
+        Sphere {
+            origin: Vec3,
+            radius: f32,
+            material: Material,
+        },
+        Cuboid {
+            origin: Vec3,
+            half_extents: Vec3,
+            material: Material,
+        },
+        Plane {
+            origin: Vec3,
+            normal: Vec3,
+            material: Material,
+        },
+        Torus {
+            origin: Vec3,
+            normal: Vec3,
+            major_radius: f32,
+            minor_radius: f32,
+            material: Material,
+        },
+        Union(Box<Sdf>, Box<Sdf>),
+        Intersection(Box<Sdf>, Box<Sdf>),
+        Subtraction(Box<Sdf>, Box<Sdf>),
+        SmoothUnion(Box<Sdf>, Box<Sdf>, f32),
+        SmoothIntersection(Box<Sdf>, Box<Sdf>, f32),
+        SmoothSubtraction(Box<Sdf>, Box<Sdf>, f32),
+    }
+
+    impl Sdf {
+        pub fn sphere(origin: Vec3, radius: f32, material: Material) -> Self {
+            Self::Sphere {
+                origin,
+                radius,
+                material,
+            }
+        }
+
+        pub fn cuboid(origin: Vec3, half_extents: Vec3, material: Material) -> Self {
+            Self::Cuboid {
+                origin,
+                half_extents,
+                material,
+            }
+        }
+
+        pub fn plane(origin: Vec3, normal: Vec3, material: Material) -> Self {
+            Self::Plane {
+                origin,
+                normal,
+                material,
+            }
+        }
+
+        pub fn torus(
+            origin: Vec3,
+            normal: Vec3,
+            major_radius: f32,
+            minor_radius: f32,
+            material: Material,
+        ) -> Self {
+            Self::Torus {
+                origin,
+                normal,
+                major_radius,
+                minor_radius,
+                material,
+            }
+        }
+
+        pub fn union(a: Sdf, b: Sdf) -> Self {
+            Self::Union(Box::new(a), Box::new(b))
+        }
+
+        pub fn intersection(a: Sdf, b: Sdf) -> Self {
+            Self::Intersection(Box::new(a), Box::new(b))
+        }
+
+        pub fn subtraction(a: Sdf, b: Sdf) -> Self {
+            Self::Subtraction(Box::new(a), Box::new(b))
+        }
+
+        pub fn smooth_union(a: Sdf, b: Sdf, k: f32) -> Self {
+            Self::SmoothUnion(Box::new(a), Box::new(b), k)
+        }
+
+        pub fn smooth_intersection(a: Sdf, b: Sdf, k: f32) -> Self {
+            Self::SmoothIntersection(Box::new(a), Box::new(b), k)
+        }
+
+        pub fn smooth_subtraction(a: Sdf, b: Sdf, k: f32) -> Self {
+            Self::SmoothSubtraction(Box::new(a), Box::new(b), k)
+        }
+
+        pub fn distance(&self, pos: Vec3) -> f32 {
+            self.distance_material(pos).0
+        }
+
+        /// Like [`Sdf::distance`], but also returns the [`Material`] of the
+        /// primitive that the returned distance came from. For `Union` and
+        /// `Intersection` (and their smooth forms) this is the material of
+        /// whichever branch determines the result, e.g. the nearer child for
+        /// `Union`; `Subtraction` and `SmoothSubtraction` always return the
+        /// first operand's material, including on the surface carved out by
+        /// the second.
+        pub fn distance_material(&self, pos: Vec3) -> (f32, Material) {
+            match self {
+                Self::Sphere {
+                    origin,
+                    radius,
+                    material,
+                } => (sphere(pos, *origin, *radius), *material),
+                Self::Cuboid {
+                    origin,
+                    half_extents,
+                    material,
+                } => (cuboid(pos, *origin, *half_extents), *material),
+                Self::Plane {
+                    origin,
+                    normal,
+                    material,
+                } => (plane(pos, *origin, *normal), *material),
+                Self::Torus {
+                    origin,
+                    normal,
+                    major_radius,
+                    minor_radius,
+                    material,
+                } => (
+                    torus(pos, *origin, *normal, *major_radius, *minor_radius),
+                    *material,
+                ),
+                Self::Union(a, b) => {
+                    let (da, ma) = a.distance_material(pos);
+                    let (db, mb) = b.distance_material(pos);
+                    if da <= db { (da, ma) } else { (db, mb) }
+                }
+                Self::Intersection(a, b) => {
+                    let (da, ma) = a.distance_material(pos);
+                    let (db, mb) = b.distance_material(pos);
+                    if da >= db { (da, ma) } else { (db, mb) }
+                }
+                Self::Subtraction(a, b) => {
+                    let (da, ma) = a.distance_material(pos);
+                    let (db, _) = b.distance_material(pos);
+                    if da >= -db { (da, ma) } else { (-db, ma) }
+                }
+                Self::SmoothUnion(a, b, k) => {
+                    let (da, ma) = a.distance_material(pos);
+                    let (db, mb) = b.distance_material(pos);
+                    (smin(da, db, *k), if da <= db { ma } else { mb })
+                }
+                Self::SmoothIntersection(a, b, k) => {
+                    let (da, ma) = a.distance_material(pos);
+                    let (db, mb) = b.distance_material(pos);
+                    (-smin(-da, -db, *k), if da >= db { ma } else { mb })
+                }
+                Self::SmoothSubtraction(a, b, k) => {
+                    let (da, ma) = a.distance_material(pos);
+                    let (db, _) = b.distance_material(pos);
+                    (-smin(-da, db, *k), ma)
+                }
+            }
+        }
+    }
+}
+
+mod raytrace {
+    use glam::Vec3;
+
+    use crate::lighting::{PointLight, SoftShadowOptions, point_light_diffuse};
+    use crate::objects::Sdf;
+    use crate::raymarch::{Ray, RaymarchOptions, RaymarchResult, raymarch};
+
+    /// Reflects `direction` about `normal`.
+    fn reflect(direction: Vec3, normal: Vec3) -> Vec3 {
+        direction - 2.0 * direction.dot(normal) * normal
+    }
+
+    /// Refracts `direction` through a surface via Snell's law, given the
+    /// ratio `n1 / n2` of the indices of refraction either side of it.
+    /// Returns `None` on total internal reflection.
+    fn refract(direction: Vec3, normal: Vec3, ior_ratio: f32) -> Option<Vec3> {
+        let cos_i = (-direction).dot(normal).clamp(-1.0, 1.0);
+        let sin2_t = ior_ratio * ior_ratio * (1.0 - cos_i * cos_i);
+        if sin2_t > 1.0 {
+            return None;
+        }
+        let cos_t = (1.0 - sin2_t).sqrt();
+        Some(direction * ior_ratio + normal * (ior_ratio * cos_i - cos_t))
+    }
+
+    /// Schlick's approximation of the Fresnel reflectance at a dielectric
+    /// boundary between media of index `n1` and `n2`.
+    fn schlick(cos_i: f32, n1: f32, n2: f32) -> f32 {
+        let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos_i).powi(5)
+    }
+
+    /// Traces `ray` through `scene`, recursing into mirror reflections and
+    /// dielectric refractions up to `raymarch_options.max_bounces` deep.
+    /// Matte surfaces (and the diffuse term of mirror-reflective ones) are
+    /// shaded with [`point_light_diffuse`]; dielectrics return only the
+    /// Fresnel blend of their reflected and refracted rays, falling back to
+    /// the matte shade once `max_bounces` is reached. Rays that miss the
+    /// scene contribute black.
+    pub fn trace(
+        ray: Ray,
+        scene: &Sdf,
+        raymarch_options: &RaymarchOptions,
+        shadow_options: &SoftShadowOptions,
+        lights: &[PointLight],
+        bounce: usize,
+    ) -> Vec3 {
+        let distance_fn = |pos: Vec3| scene.distance(pos);
+        let RaymarchResult::HitScene { depth, normal, .. } =
+            raymarch(ray, distance_fn, raymarch_options)
+        else {
+            return Vec3::ZERO;
+        };
+
+        let hit_point = ray.at(depth);
+        let material = scene.distance_material(hit_point).1;
+
+        let diffuse: f32 = lights
+            .iter()
+            .map(|light| {
+                point_light_diffuse(
+                    hit_point,
+                    normal,
+                    light,
+                    &distance_fn,
+                    raymarch_options,
+                    shadow_options,
+                )
+            })
+            .sum();
+        let matte_color = material.albedo * diffuse;
+
+        if bounce >= raymarch_options.max_bounces {
+            return matte_color;
+        }
+
+        let bias = normal * raymarch_options.epsilon * 2.0;
+
+        if let Some(ior) = material.ior {
+            let (n1, n2, oriented_normal) = if ray.direction.dot(normal) < 0.0 {
+                (1.0, ior, normal)
+            } else {
+                (ior, 1.0, -normal)
+            };
+            let bias = oriented_normal * raymarch_options.epsilon * 2.0;
+            let cos_i = (-ray.direction).dot(oriented_normal).clamp(-1.0, 1.0);
+            let fresnel = schlick(cos_i, n1, n2);
+
+            let reflected = trace(
+                Ray {
+                    origin: hit_point + bias,
+                    direction: reflect(ray.direction, oriented_normal),
+                },
+                scene,
+                raymarch_options,
+                shadow_options,
+                lights,
+                bounce + 1,
+            );
+
+            return match refract(ray.direction, oriented_normal, n1 / n2) {
+                Some(refracted_dir) => {
+                    let refracted = trace(
+                        Ray {
+                            origin: hit_point - bias,
+                            direction: refracted_dir,
+                        },
+                        scene,
+                        raymarch_options,
+                        shadow_options,
+                        lights,
+                        bounce + 1,
+                    );
+                    reflected * fresnel + refracted * (1.0 - fresnel)
+                }
+                None => reflected,
+            };
+        }
+
+        if material.reflectivity > 0.0 {
+            let reflected = trace(
+                Ray {
+                    origin: hit_point + bias,
+                    direction: reflect(ray.direction, normal),
+                },
+                scene,
+                raymarch_options,
+                shadow_options,
+                lights,
+                bounce + 1,
+            );
+            return matte_color * (1.0 - material.reflectivity) + reflected * material.reflectivity;
+        }
+
+        matte_color
+    }
+}
+
+mod pathtrace {
+    use glam::Vec3;
+    use rand::Rng;
+
+    use crate::lighting::{PointLight, SoftShadowOptions, point_light_diffuse};
+    use crate::objects::Sdf;
+    use crate::raymarch::{Ray, RaymarchOptions, RaymarchResult, raymarch};
+
+    pub struct PathtraceOptions {
+        /// Hard cap on how many diffuse bounces a path may take before it's
+        /// terminated, regardless of Russian roulette.
+        pub max_bounces: usize,
+        /// Paths traced per pixel on every accumulated frame.
+        pub rays_per_pixel: usize,
+        /// Bounce index at which Russian-roulette termination kicks in.
+        pub roulette_start: usize,
+    }
+
+    impl Default for PathtraceOptions {
+        fn default() -> Self {
+            Self {
+                max_bounces: 8,
+                rays_per_pixel: 1,
+                roulette_start: 3,
+            }
+        }
+    }
+
+    /// Samples a cosine-weighted direction in the hemisphere around
+    /// `normal`: draws `u1, u2 ~ U(0,1)`, maps them to a unit-disk point
+    /// `(r cos phi, r sin phi)` with `r = sqrt(u1)`, `phi = 2*pi*u2`, lifts
+    /// it to the hemisphere with `z = sqrt(1 - u1)`, then rotates that local
+    /// direction into the tangent frame around `normal`.
+    fn cosine_sample_hemisphere(normal: Vec3, rng: &mut impl Rng) -> Vec3 {
+        let u1: f32 = rng.random();
+        let u2: f32 = rng.random();
+        let r = u1.sqrt();
+        let phi = std::f32::consts::TAU * u2;
+        let (x, y, z) = (r * phi.cos(), r * phi.sin(), (1.0 - u1).sqrt());
+
+        let tangent = if normal.x.abs() > 0.9 { Vec3::Y } else { Vec3::X };
+        let bitangent = normal.cross(tangent).normalize();
+        let tangent = bitangent.cross(normal);
+        tangent * x + bitangent * y + normal * z
+    }
+
+    /// Traces one diffuse path through `scene`: at each hit, adds the
+    /// throughput-weighted direct light contribution (shaded the same way
+    /// as `raytrace::trace`'s matte term), multiplies the throughput by the
+    /// surface's albedo, and continues in a cosine-weighted random
+    /// direction until it escapes the scene, hits `max_bounces`, or is
+    /// killed by Russian roulette.
+    pub fn trace_path(
+        mut ray: Ray,
+        scene: &Sdf,
+        raymarch_options: &RaymarchOptions,
+        shadow_options: &SoftShadowOptions,
+        lights: &[PointLight],
+        options: &PathtraceOptions,
+        rng: &mut impl Rng,
+    ) -> Vec3 {
+        let distance_fn = |pos: Vec3| scene.distance(pos);
+        let mut throughput = Vec3::ONE;
+        let mut radiance = Vec3::ZERO;
+
+        for bounce in 0..options.max_bounces {
+            let RaymarchResult::HitScene { depth, normal, .. } =
+                raymarch(ray, distance_fn, raymarch_options)
+            else {
+                break;
+            };
+
+            let hit_point = ray.at(depth);
+            let material = scene.distance_material(hit_point).1;
+
+            let direct: f32 = lights
+                .iter()
+                .map(|light| {
+                    point_light_diffuse(
+                        hit_point,
+                        normal,
+                        light,
+                        &distance_fn,
+                        raymarch_options,
+                        shadow_options,
+                    )
+                })
+                .sum();
+            radiance += throughput * material.albedo * direct;
+            throughput *= material.albedo;
+
+            if bounce >= options.roulette_start {
+                let survive = throughput.max_element().clamp(0.05, 1.0);
+                if rng.random::<f32>() > survive {
+                    break;
+                }
+                throughput /= survive;
+            }
+
+            ray = Ray {
+                origin: hit_point + normal * raymarch_options.epsilon * 2.0,
+                direction: cosine_sample_hemisphere(normal, rng),
+            };
+        }
+
+        radiance
+    }
+
+    /// Persistent per-pixel radiance accumulator for progressive rendering:
+    /// call [`AccumulationBuffer::add_sample`] for every path traced through
+    /// a pixel while the camera and scene are still, and
+    /// [`AccumulationBuffer::resolve`] to read back the running average at
+    /// any time, so the image converges the longer it accumulates.
+    pub struct AccumulationBuffer<const W: usize, const H: usize> {
+        sum: Box<[[Vec3; W]; H]>,
+        samples: usize,
+    }
+
+    impl<const W: usize, const H: usize> AccumulationBuffer<W, H> {
+        pub fn new() -> Self {
+            Self {
+                sum: vec![[Vec3::ZERO; W]; H].into_boxed_slice().try_into().unwrap(),
+                samples: 0,
+            }
+        }
+
+        pub fn add_sample(&mut self, x: usize, y: usize, color: Vec3) {
+            self.sum[y][x] += color;
+        }
+
+        /// Marks that `samples_added` more samples have been added to every
+        /// pixel, so [`AccumulationBuffer::resolve`] divides by the right
+        /// count.
+        pub fn advance_frame(&mut self, samples_added: usize) {
+            self.samples += samples_added;
+        }
+
+        pub fn resolve(&self, x: usize, y: usize) -> Vec3 {
+            if self.samples == 0 {
+                Vec3::ZERO
+            } else {
+                self.sum[y][x] / self.samples as f32
+            }
+        }
+    }
+}
+
+mod lighting {
+    use glam::Vec3;
+
+    use crate::raymarch::RaymarchOptions;
+
+    pub struct PointLight {
+        pub position: Vec3,
+        pub intensity: f32,
+    }
+
+    pub struct SoftShadowOptions {
+        /// Distance along the light ray to start marching from, offset just
+        /// off the surface to avoid immediately self-shadowing.
+        pub mint: f32,
+        /// Controls penumbra hardness: larger values give sharper shadows.
+        pub k: f32,
+    }
+
+    impl Default for SoftShadowOptions {
+        fn default() -> Self {
+            Self { mint: 0.02, k: 8.0 }
+        }
+    }
+
+    /// Marches a secondary ray from `origin` towards a light `dist_to_light`
+    /// units away along `light_dir`, returning a soft penumbra factor in
+    /// `[0, 1]`: `0.0` where the light is fully blocked, `1.0` where it has
+    /// an unobstructed line of sight, and values in between as the ray
+    /// grazes past nearby occluders.
+    pub fn soft_shadow(
+        origin: Vec3,
+        light_dir: Vec3,
+        dist_to_light: f32,
+        scene: &impl Fn(Vec3) -> f32,
+        raymarch_options: &RaymarchOptions,
+        shadow_options: &SoftShadowOptions,
+    ) -> f32 {
+        let mut t = shadow_options.mint;
+        let mut res = 1.0f32;
+        while t < dist_to_light {
+            let h = scene(origin + light_dir * t);
+            if h < raymarch_options.epsilon {
+                return 0.0;
+            }
+            res = res.min(shadow_options.k * h / t);
+            t += h;
+        }
+        res.clamp(0.0, 1.0)
+    }
+
+    /// Diffuse (`N·L`) contribution of a single point light at a surface
+    /// point, attenuated by [`soft_shadow`] cast towards that light.
+    pub fn point_light_diffuse(
+        hit_point: Vec3,
+        normal: Vec3,
+        light: &PointLight,
+        scene: &impl Fn(Vec3) -> f32,
+        raymarch_options: &RaymarchOptions,
+        shadow_options: &SoftShadowOptions,
+    ) -> f32 {
+        let to_light = light.position - hit_point;
+        let dist_to_light = to_light.length();
+        let light_dir = to_light / dist_to_light;
+        let shadow = soft_shadow(
+            hit_point,
+            light_dir,
+            dist_to_light,
+            scene,
+            raymarch_options,
+            shadow_options,
+        );
+        let diffuse = normal.dot(light_dir).max(0.0);
+        diffuse * shadow * light.intensity
+    }
 }
 
 mod camera {
-    use glam::{Mat3, Vec3};
+    use glam::{Mat3, Vec3, vec3};
+    use rand::Rng;
 
     use crate::raymarch::{Ray, RaymarchOptions, RaymarchResult, raymarch};
 
+    /// Default number of jittered rays averaged per pixel when the camera
+    /// has a non-zero [`Camera::lens_radius`].
+    pub const DEFAULT_DOF_SAMPLES: usize = 16;
+
+    #[derive(Clone, Copy)]
     pub struct Camera<const W: usize, const H: usize> {
         pub origin: Vec3,
         pub basis: Mat3,
+        /// Half the horizontal field of view's tangent, at the focus plane.
+        pub half_width: f32,
+        /// Half the vertical field of view's tangent, at the focus plane.
+        pub half_height: f32,
+        /// Aperture / 2; `0.0` gives a pinhole camera with everything in
+        /// perfect focus.
+        pub lens_radius: f32,
+        /// Distance from `origin` along the view direction that is in
+        /// perfect focus.
+        pub focus_distance: f32,
+        /// Rays averaged per pixel to approximate the lens integral; only
+        /// matters when `lens_radius > 0.0`.
+        pub dof_samples: usize,
+    }
+
+    impl<const W: usize, const H: usize> Camera<W, H> {
+        /// Builds a thin-lens camera looking from `lookfrom` towards
+        /// `lookat`, with `up` disambiguating roll. `vfov_degrees` is the
+        /// full vertical field of view. `aperture` and `focus_distance`
+        /// control depth of field: a wider aperture blurs everything that
+        /// isn't `focus_distance` away from `lookfrom`.
+        pub fn new(
+            lookfrom: Vec3,
+            lookat: Vec3,
+            up: Vec3,
+            vfov_degrees: f32,
+            aperture: f32,
+            focus_distance: f32,
+        ) -> Self {
+            let half_height = (vfov_degrees.to_radians() / 2.0).tan();
+            let half_width = half_height * (W as f32 / H as f32);
+
+            let forward = (lookat - lookfrom).normalize();
+            let right = forward.cross(up).normalize();
+            let true_up = right.cross(forward);
+
+            Self {
+                origin: lookfrom,
+                basis: Mat3::from_cols(right, true_up, forward),
+                half_width,
+                half_height,
+                lens_radius: aperture / 2.0,
+                focus_distance,
+                dof_samples: DEFAULT_DOF_SAMPLES,
+            }
+        }
+    }
+
+    /// Uniformly samples a point within the unit disk via rejection
+    /// sampling.
+    fn random_in_unit_disk(rng: &mut impl Rng) -> Vec3 {
+        loop {
+            let p = vec3(
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                0.0,
+            );
+            if p.length_squared() < 1.0 {
+                return p;
+            }
+        }
+    }
+
+    impl<const W: usize, const H: usize> Camera<W, H> {
+        /// Generates one (possibly lens-jittered) primary ray through pixel
+        /// `(x_pixel, y_pixel)`, for callers that want to march rays
+        /// themselves (e.g. `pathtrace::trace_path`) instead of going
+        /// through [`Camera::render_parallel`].
+        pub fn primary_ray(&self, x_pixel: usize, y_pixel: usize, rng: &mut impl Rng) -> Ray {
+            let x = ((x_pixel as f32) / (W as f32) - 0.5) * 2.0 * self.half_width;
+            let y = ((y_pixel as f32) / (H as f32) - 0.5) * 2.0 * self.half_height;
+            let pixel_direction = self.basis * Vec3 { x, y, z: 1.0 }.normalize();
+            let focal_point = self.origin + self.focus_distance * pixel_direction;
+
+            let lens_offset = if self.lens_radius > 0.0 {
+                self.basis * random_in_unit_disk(rng) * self.lens_radius
+            } else {
+                Vec3::ZERO
+            };
+            let origin = self.origin + lens_offset;
+            let direction = (focal_point - origin).normalize();
+            Ray { origin, direction }
+        }
     }
 
+    /// Heap-allocated so large resolutions (e.g. the `--output` still) don't
+    /// blow the stack: at `640x480` these four arrays alone total roughly
+    /// 9.8 MB, far more than a thread's default stack size.
     #[derive(Debug)]
     pub struct RenderResult<const W: usize, const H: usize> {
-        pub depth: [[f32; W]; H],
-        pub proximity: [[f32; W]; H],
-        pub normals: [[Vec3; W]; H],
+        pub depth: Box<[[f32; W]; H]>,
+        pub proximity: Box<[[f32; W]; H]>,
+        pub normals: Box<[[Vec3; W]; H]>,
+        /// World-space hit point, valid wherever `depth` is finite.
+        pub positions: Box<[[Vec3; W]; H]>,
     }
 
     impl<const W: usize, const H: usize> RenderResult<W, H> {
         pub fn new() -> RenderResult<W, H> {
             RenderResult {
-                depth: [[0.0; W]; H],
-                proximity: [[0.0; W]; H],
-                normals: [[Vec3::ZERO; W]; H],
+                depth: vec![[0.0; W]; H].into_boxed_slice().try_into().unwrap(),
+                proximity: vec![[0.0; W]; H].into_boxed_slice().try_into().unwrap(),
+                normals: vec![[Vec3::ZERO; W]; H]
+                    .into_boxed_slice()
+                    .try_into()
+                    .unwrap(),
+                positions: vec![[Vec3::ZERO; W]; H]
+                    .into_boxed_slice()
+                    .try_into()
+                    .unwrap(),
             }
         }
     }
 
+    /// Default size of the worker pool used by [`Camera::render_parallel`].
+    pub const THREAD_COUNT: usize = 8;
+    /// Number of row-slices handed to each worker thread by
+    /// [`Camera::render_parallel`]; it is fixed and cannot be set per call.
+    pub const SLICES_PER_THREAD: usize = 4;
+
+    /// A disjoint band of a [`RenderResult`]'s output arrays, bundled so
+    /// [`Camera::render_rows`] doesn't have to take each array as its own
+    /// parameter.
+    struct RowSlices<'a, const W: usize> {
+        depth: &'a mut [[f32; W]],
+        proximity: &'a mut [[f32; W]],
+        normals: &'a mut [[Vec3; W]],
+        positions: &'a mut [[Vec3; W]],
+    }
+
     impl<const W: usize, const H: usize> Camera<W, H> {
-        pub fn render(
+        /// Renders a full frame by splitting the `H` rows into contiguous
+        /// slices and farming them out to a fixed pool of `threads` worker
+        /// threads (each handling up to [`SLICES_PER_THREAD`] slices), so no
+        /// locking of the pixel buffers is required: every worker owns a
+        /// disjoint, non-overlapping region of the output arrays.
+        pub fn render_parallel(
             &self,
-            scene: impl Fn(Vec3) -> f32,
+            scene: impl Fn(Vec3) -> f32 + Sync,
             raymarch_options: &RaymarchOptions,
+            threads: usize,
         ) -> RenderResult<W, H> {
             let mut render_result = RenderResult::new();
-            let z = 1.0;
-            for y_pixel in 0..H {
-                let y = (y_pixel as f32) / (H as f32) - 0.5;
+            let threads = threads.max(1);
+            let rows_per_slice = H.div_ceil(threads * SLICES_PER_THREAD).max(1);
+            let rows_per_thread = rows_per_slice * SLICES_PER_THREAD;
+
+            let mut depth_rest = &mut render_result.depth[..];
+            let mut proximity_rest = &mut render_result.proximity[..];
+            let mut normals_rest = &mut render_result.normals[..];
+            let mut positions_rest = &mut render_result.positions[..];
+            let scene = &scene;
+
+            std::thread::scope(|scope| {
+                let mut row_start = 0;
+                while !depth_rest.is_empty() {
+                    let split = rows_per_thread.min(depth_rest.len());
+                    let (depth_slice, depth_tail) = depth_rest.split_at_mut(split);
+                    let (proximity_slice, proximity_tail) = proximity_rest.split_at_mut(split);
+                    let (normals_slice, normals_tail) = normals_rest.split_at_mut(split);
+                    let (positions_slice, positions_tail) = positions_rest.split_at_mut(split);
+                    depth_rest = depth_tail;
+                    proximity_rest = proximity_tail;
+                    normals_rest = normals_tail;
+                    positions_rest = positions_tail;
+
+                    let camera = *self;
+                    scope.spawn(move || {
+                        Self::render_rows(
+                            camera,
+                            scene,
+                            raymarch_options,
+                            row_start,
+                            RowSlices {
+                                depth: depth_slice,
+                                proximity: proximity_slice,
+                                normals: normals_slice,
+                                positions: positions_slice,
+                            },
+                        );
+                    });
+                    row_start += split;
+                }
+            });
+
+            render_result
+        }
+
+        /// Fills in a contiguous, disjoint band of rows starting at
+        /// `row_offset`, called by [`Camera::render_parallel`] once per
+        /// worker thread. When `camera.lens_radius` is `0.0` this traces one
+        /// pinhole ray per pixel; otherwise it averages `camera.dof_samples`
+        /// thin-lens rays per pixel for a depth-of-field blur.
+        fn render_rows(
+            camera: Self,
+            scene: &impl Fn(Vec3) -> f32,
+            raymarch_options: &RaymarchOptions,
+            row_offset: usize,
+            rows: RowSlices<'_, W>,
+        ) {
+            let mut rng = rand::rng();
+            let samples = if camera.lens_radius > 0.0 {
+                camera.dof_samples.max(1)
+            } else {
+                1
+            };
+            for (dy, (((depth_row, proximity_row), normal_row), position_row)) in rows
+                .depth
+                .iter_mut()
+                .zip(rows.proximity.iter_mut())
+                .zip(rows.normals.iter_mut())
+                .zip(rows.positions.iter_mut())
+                .enumerate()
+            {
+                let y_pixel = row_offset + dy;
                 for x_pixel in 0..W {
-                    let x = (x_pixel as f32) / (W as f32) - 0.5;
-                    let direction = Vec3 { x, y, z }.normalize();
-                    let direction = self.basis * direction;
-                    let origin = self.origin;
-                    let ray = Ray { direction, origin };
-                    let res_depth = &mut render_result.depth[y_pixel][x_pixel];
-                    let res_proximity = &mut render_result.proximity[y_pixel][x_pixel];
-                    let res_normals = &mut render_result.normals[y_pixel][x_pixel];
-                    match raymarch(ray, &scene, raymarch_options) {
-                        RaymarchResult::MissedScene {
-                            nearest_distance, ..
-                        } => {
-                            *res_depth = f32::INFINITY;
-                            *res_proximity = nearest_distance;
-                        }
-                        RaymarchResult::ReachedMaxIterations { nearest_distance } => {
-                            *res_depth = f32::NAN;
-                            *res_proximity = nearest_distance;
-                        }
-                        RaymarchResult::HitScene { depth, normal, .. } => {
-                            *res_depth = depth;
-                            *res_normals = normal;
+                    let mut hit_count = 0usize;
+                    let mut depth_sum = 0.0;
+                    let mut normal_sum = Vec3::ZERO;
+                    let mut position_sum = Vec3::ZERO;
+                    let mut last_nearest_distance = raymarch_options.far_clip;
+
+                    for _ in 0..samples {
+                        let ray = camera.primary_ray(x_pixel, y_pixel, &mut rng);
+                        match raymarch(ray, scene, raymarch_options) {
+                            RaymarchResult::HitScene { depth, normal, .. } => {
+                                hit_count += 1;
+                                depth_sum += depth;
+                                normal_sum += normal;
+                                position_sum += ray.at(depth);
+                            }
+                            RaymarchResult::MissedScene {
+                                nearest_distance, ..
+                            }
+                            | RaymarchResult::ReachedMaxIterations { nearest_distance } => {
+                                last_nearest_distance = nearest_distance;
+                            }
                         }
                     }
+
+                    let res_depth = &mut depth_row[x_pixel];
+                    let res_proximity = &mut proximity_row[x_pixel];
+                    let res_normal = &mut normal_row[x_pixel];
+                    let res_position = &mut position_row[x_pixel];
+                    if hit_count > 0 {
+                        let hit_count = hit_count as f32;
+                        *res_depth = depth_sum / hit_count;
+                        *res_normal = (normal_sum / hit_count).normalize();
+                        *res_position = position_sum / hit_count;
+                        *res_proximity = 0.0;
+                    } else {
+                        *res_depth = f32::INFINITY;
+                        *res_proximity = last_nearest_distance;
+                    }
                 }
             }
-            render_result
         }
     }
 }
 
+mod image_output {
+    use std::path::Path;
+
+    use glam::Vec3;
+    use image::{ImageResult, Rgb, RgbImage};
+
+    use crate::camera::{Camera, RenderResult};
+    use crate::lighting::{PointLight, SoftShadowOptions, point_light_diffuse};
+    use crate::objects::Sdf;
+    use crate::raymarch::RaymarchOptions;
+    use crate::raytrace;
+
+    /// Shades a [`RenderResult`] with the same point-light and soft-shadow
+    /// model as the ASCII preview, mapping each hit pixel's surface normal
+    /// to a base color (`normal * 0.5 + 0.5`, the usual normal-map tint) that
+    /// the diffuse brightness then darkens, and misses to black.
+    pub fn to_rgb_image<const W: usize, const H: usize>(
+        result: &RenderResult<W, H>,
+        scene: &impl Fn(Vec3) -> f32,
+        raymarch_options: &RaymarchOptions,
+        shadow_options: &SoftShadowOptions,
+        lights: &[PointLight],
+    ) -> RgbImage {
+        let mut image = RgbImage::new(W as u32, H as u32);
+        for y in 0..H {
+            for x in 0..W {
+                let depth = result.depth[y][x];
+                let pixel = if depth.is_finite() {
+                    let normal = result.normals[y][x];
+                    let position = result.positions[y][x];
+                    let brightness: f32 = lights
+                        .iter()
+                        .map(|light| {
+                            point_light_diffuse(
+                                position,
+                                normal,
+                                light,
+                                scene,
+                                raymarch_options,
+                                shadow_options,
+                            )
+                        })
+                        .sum();
+                    let normal_color = normal * 0.5 + Vec3::splat(0.5);
+                    let shaded = normal_color * brightness.clamp(0.0, 1.0);
+                    Rgb([
+                        radiance_to_channel(shaded.x),
+                        radiance_to_channel(shaded.y),
+                        radiance_to_channel(shaded.z),
+                    ])
+                } else {
+                    Rgb([0, 0, 0])
+                };
+                image.put_pixel(x as u32, y as u32, pixel);
+            }
+        }
+        image
+    }
+
+    /// Maps a linear radiance channel to an 8-bit pixel channel, clamping to
+    /// `[0, 1]`.
+    fn radiance_to_channel(value: f32) -> u8 {
+        (value.clamp(0.0, 1.0) * 255.0) as u8
+    }
+
+    /// Renders `scene` through `camera` with [`raytrace::trace`] instead of
+    /// [`to_rgb_image`]'s single-bounce `RenderResult` shading, so mirror
+    /// reflections and dielectric refractions on the scene's materials show
+    /// up in the output.
+    pub fn to_rgb_image_raytraced<const W: usize, const H: usize>(
+        camera: &Camera<W, H>,
+        scene: &Sdf,
+        raymarch_options: &RaymarchOptions,
+        shadow_options: &SoftShadowOptions,
+        lights: &[PointLight],
+    ) -> RgbImage {
+        let mut image = RgbImage::new(W as u32, H as u32);
+        let mut rng = rand::rng();
+        for y in 0..H {
+            for x in 0..W {
+                let ray = camera.primary_ray(x, y, &mut rng);
+                let color =
+                    raytrace::trace(ray, scene, raymarch_options, shadow_options, lights, 0);
+                image.put_pixel(
+                    x as u32,
+                    y as u32,
+                    Rgb([
+                        radiance_to_channel(color.x),
+                        radiance_to_channel(color.y),
+                        radiance_to_channel(color.z),
+                    ]),
+                );
+            }
+        }
+        image
+    }
+
+    /// Writes `image` out, inferring the file format from `path`'s
+    /// extension.
+    pub fn write_image(image: &RgbImage, path: &Path) -> ImageResult<()> {
+        image.save(path)
+    }
+}
+
+/// Axis of the spinning torus at a given animation `time`, shared by
+/// [`scene_at`] and [`sdf_scene_at`].
+fn torus_normal_at(time: f32) -> Vec3 {
+    let normal = vec3(0.0, 0.0, 1.0);
+    let normal = Mat3::from_axis_angle(vec3(0.0, 1.0, 0.0), time / 3.0) * normal;
+    let normal = Mat3::from_axis_angle(vec3(1.0, 0.0, 0.0), time / 10.2467) * normal;
+    Mat3::from_axis_angle(vec3(0.0, 0.0, 1.0), (time / 13.2251f32).sin() * 6.0) * normal
+}
+
+/// Builds the spinning-torus scene at a given animation `time`, shared by
+/// the live ASCII preview and the `--output` still renderer.
+fn scene_at(time: f32) -> impl Fn(Vec3) -> f32 + Sync + Copy {
+    let normal = torus_normal_at(time);
+    move |pos| torus(pos, vec3(0.0, 0.0, 10.0), normal, 3.0, 1.0)
+}
+
+/// The spinning torus from [`scene_at`] with a bite taken out of it, next to
+/// a trophy (a ball smooth-unioned onto a rounded pedestal) and a dipped
+/// floor, all clipped to a bounding sphere — a single [`Sdf`] tree that
+/// exercises every primitive and combinator, so it can feed `raytrace::trace`
+/// or `pathtrace::trace_path`.
+fn sdf_scene_at(time: f32) -> Sdf {
+    let torus = Sdf::torus(
+        vec3(0.0, 0.0, 10.0),
+        torus_normal_at(time),
+        3.0,
+        1.0,
+        Material::MATTE.with_albedo(vec3(0.85, 0.35, 0.25)),
+    );
+    let bite = Sdf::sphere(vec3(3.0, 0.0, 8.5), 1.5, Material::MATTE);
+    let notched_torus = Sdf::subtraction(torus, bite);
+
+    let ball = Sdf::sphere(vec3(-5.0, -1.0, 8.0), 1.3, Material::dielectric(1.5));
+    let pedestal = Sdf::cuboid(
+        vec3(-5.0, -3.0, 8.0),
+        vec3(1.4, 1.4, 1.4),
+        Material::mirror(0.5),
+    );
+    let pedestal_bounds = Sdf::sphere(vec3(-5.0, -3.0, 8.0), 2.2, Material::mirror(0.5));
+    let rounded_pedestal = Sdf::smooth_intersection(pedestal, pedestal_bounds, 0.4);
+    let trophy = Sdf::smooth_union(ball, rounded_pedestal, 0.5);
+
+    let floor = Sdf::plane(
+        vec3(0.0, -4.0, 0.0),
+        Vec3::Y,
+        Material::MATTE.with_albedo(vec3(0.3, 0.32, 0.36)),
+    );
+    let dip = Sdf::sphere(vec3(0.0, -4.0, 10.0), 2.5, Material::MATTE);
+    let dipped_floor = Sdf::smooth_subtraction(floor, dip, 1.0);
+
+    let scene = Sdf::union(Sdf::union(notched_torus, trophy), dipped_floor);
+    let bounds = Sdf::sphere(vec3(0.0, 0.0, 9.0), 30.0, Material::MATTE);
+    Sdf::intersection(scene, bounds)
+}
+
 fn main() {
     const WIDTH: usize = 60;
     const HEIGHT: usize = 30;
+    // Resolution used for `--output` stills, decoupled from the terminal's
+    // tiny fixed size above.
+    const OUTPUT_WIDTH: usize = 640;
+    const OUTPUT_HEIGHT: usize = 480;
 
-    let camera: Camera<WIDTH, HEIGHT> = Camera {
-        origin: vec3(0.0, 0.0, 0.0),
-        basis: mat3(Vec3::X, Vec3::Y, Vec3::Z),
-    };
-    let start = Instant::now();
     let options = RaymarchOptions::default();
+    let shadow_options = lighting::SoftShadowOptions::default();
+    let lights = [
+        PointLight {
+            position: vec3(-8.0, -6.0, 4.0),
+            intensity: 1.0,
+        },
+        PointLight {
+            position: vec3(6.0, 4.0, -2.0),
+            intensity: 0.4,
+        },
+    ];
+
+    let args: Vec<String> = std::env::args().collect();
+    let output_path = args
+        .iter()
+        .position(|arg| arg == "--output")
+        .and_then(|i| args.get(i + 1));
+
+    if let Some(output_path) = output_path {
+        let camera: Camera<OUTPUT_WIDTH, OUTPUT_HEIGHT> = Camera::new(
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 10.0),
+            Vec3::Y,
+            53.13,
+            0.15,
+            10.0,
+        );
+        let scene = scene_at(0.0);
+        let result = camera.render_parallel(scene, &options, camera::THREAD_COUNT);
+        let image = image_output::to_rgb_image(&result, &scene, &options, &shadow_options, &lights);
+        image_output::write_image(&image, std::path::Path::new(output_path))
+            .expect("failed to write output image");
+        return;
+    }
+
+    let raytrace_output_path = args
+        .iter()
+        .position(|arg| arg == "--raytrace")
+        .and_then(|i| args.get(i + 1));
+
+    if let Some(raytrace_output_path) = raytrace_output_path {
+        let camera: Camera<OUTPUT_WIDTH, OUTPUT_HEIGHT> = Camera::new(
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 10.0),
+            Vec3::Y,
+            53.13,
+            0.0,
+            10.0,
+        );
+        let scene = sdf_scene_at(0.0);
+        let image = image_output::to_rgb_image_raytraced(
+            &camera,
+            &scene,
+            &options,
+            &shadow_options,
+            &lights,
+        );
+        image_output::write_image(&image, std::path::Path::new(raytrace_output_path))
+            .expect("failed to write output image");
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--path-trace") {
+        let camera: Camera<WIDTH, HEIGHT> = Camera::new(
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 10.0),
+            Vec3::Y,
+            53.13,
+            0.0,
+            10.0,
+        );
+        // The scene is frozen so the accumulation buffer actually converges
+        // instead of chasing a moving torus.
+        let scene = sdf_scene_at(2.0);
+        let pathtrace_options = pathtrace::PathtraceOptions::default();
+        let mut buffer: pathtrace::AccumulationBuffer<WIDTH, HEIGHT> =
+            pathtrace::AccumulationBuffer::new();
+        let mut rng = rand::rng();
+
+        std::io::stdout().execute(Clear(ClearType::All)).unwrap();
+        loop {
+            std::io::stdout().execute(cursor::MoveTo(0, 0)).unwrap();
+            for y in 0..HEIGHT {
+                for x in 0..WIDTH {
+                    for _ in 0..pathtrace_options.rays_per_pixel {
+                        let ray = camera.primary_ray(x, y, &mut rng);
+                        let radiance = pathtrace::trace_path(
+                            ray,
+                            &scene,
+                            &options,
+                            &shadow_options,
+                            &lights,
+                            &pathtrace_options,
+                            &mut rng,
+                        );
+                        buffer.add_sample(x, y, radiance);
+                    }
+                }
+            }
+            buffer.advance_frame(pathtrace_options.rays_per_pixel);
+
+            for y in 0..HEIGHT {
+                let line: String = (0..WIDTH)
+                    .map(|x| {
+                        let brightness = buffer.resolve(x, y).max_element();
+                        match brightness {
+                            0.1..=0.25 => '.',
+                            0.25..=0.5 => '-',
+                            0.5..=0.75 => '+',
+                            0.75.. => '#',
+                            _ => ' ',
+                        }
+                    })
+                    .collect();
+                println!("{line}");
+            }
+        }
+    }
+
+    let camera: Camera<WIDTH, HEIGHT> = Camera::new(
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 0.0, 10.0),
+        Vec3::Y,
+        53.13,
+        0.15,
+        10.0,
+    );
+    let start = Instant::now();
 
     std::io::stdout().execute(Clear(ClearType::All)).unwrap();
     loop {
         std::io::stdout().execute(cursor::MoveTo(0, 0)).unwrap();
         let time = Instant::now().duration_since(start).as_secs_f32();
-        let scene = |pos| {
-            let normal = vec3(0.0, 0.0, 1.0);
-            let normal = Mat3::from_axis_angle(vec3(0.0, 1.0, 0.0), time / 3.0) * normal;
-            let normal = Mat3::from_axis_angle(vec3(1.0, 0.0, 0.0), time / 10.2467) * normal;
-            let normal =
-                Mat3::from_axis_angle(vec3(0.0, 0.0, 1.0), (time / 13.2251f32).sin() * 6.0)
-                    * normal;
-            torus(pos, vec3(0.0, 0.0, 10.0), normal, 3.0, 1.0)
-        };
-        let result = camera.render(scene, &options);
-        for (depth_row, normals_row) in result.depth.into_iter().zip(result.normals) {
+        let scene = scene_at(time);
+        let result = camera.render_parallel(scene, &options, camera::THREAD_COUNT);
+        for ((depth_row, normals_row), positions_row) in result
+            .depth
+            .iter()
+            .zip(result.normals.iter())
+            .zip(result.positions.iter())
+        {
             let line: String = depth_row
-                .into_iter()
-                .zip(normals_row)
-                .map(|(depth, normal)| {
+                .iter()
+                .zip(normals_row.iter())
+                .zip(positions_row.iter())
+                .map(|((&depth, &normal), &position)| {
                     let mut brightness = 0.0;
                     if depth.is_finite() {
-                        brightness = (normal.dot(vec3(0.0, -1.0, 0.0)) + 1.0) / 2.0;
+                        brightness = lights
+                            .iter()
+                            .map(|light| {
+                                lighting::point_light_diffuse(
+                                    position,
+                                    normal,
+                                    light,
+                                    &scene,
+                                    &options,
+                                    &shadow_options,
+                                )
+                            })
+                            .sum();
                     }
                     match brightness {
                         0.1..=0.25 => '.',